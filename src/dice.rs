@@ -0,0 +1,125 @@
+//! A discrete distribution over dice advance values, replacing the
+//! hard-coded uniform `1/dice_n` assumption so the solver can model any
+//! die (or combination of dice) the caller cares about.
+
+use rand::Rng;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiceDistributionError {
+    NegativeWeight(f64),
+    WeightsDontSumToOne(f64),
+}
+
+/// An explicit `(value, weight)` distribution over advance values, with
+/// weights normalized to sum to one. The weight of a value also doubles as
+/// the model's re-roll success probability for that value (see
+/// [`DiceDistribution::success_prob`]).
+#[derive(Debug, Clone)]
+pub struct DiceDistribution {
+    outcomes: Vec<(u32, f64)>,
+}
+
+impl DiceDistribution {
+    /// Builds a distribution from explicit `(value, weight)` pairs,
+    /// rejecting negative weights or weights that don't sum to one.
+    pub fn new(outcomes: Vec<(u32, f64)>) -> Result<DiceDistribution, DiceDistributionError> {
+        for &(_, weight) in &outcomes {
+            if weight < 0.0 {
+                return Err(DiceDistributionError::NegativeWeight(weight));
+            }
+        }
+        let total: f64 = outcomes.iter().map(|&(_, weight)| weight).sum();
+        if (total - 1.0).abs() > 1e-9 {
+            return Err(DiceDistributionError::WeightsDontSumToOne(total));
+        }
+        Ok(DiceDistribution { outcomes })
+    }
+
+    /// A single fair die uniform over `1..=dice`.
+    pub fn uniform(dice: u32) -> DiceDistribution {
+        let weight = 1.0 / dice as f64;
+        DiceDistribution {
+            outcomes: (1..=dice).map(|value| (value, weight)).collect(),
+        }
+    }
+
+    /// The triangular distribution of the sum of two fair six-sided dice,
+    /// matching the real Can't Stop dice (values `2..=12`).
+    pub fn two_dice_sum() -> DiceDistribution {
+        let mut counts = [0u32; 11];
+        for a in 1..=6 {
+            for b in 1..=6 {
+                counts[(a + b - 2) as usize] += 1;
+            }
+        }
+        let outcomes = counts
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| (i as u32 + 2, count as f64 / 36.0))
+            .collect();
+        DiceDistribution { outcomes }
+    }
+
+    pub fn outcomes(&self) -> &[(u32, f64)] {
+        &self.outcomes
+    }
+
+    /// The model's re-roll success probability for `value`: the weight the
+    /// distribution itself assigns to landing on `value` again.
+    pub fn success_prob(&self, value: u32) -> f64 {
+        self.outcomes
+            .iter()
+            .find(|&&(v, _)| v == value)
+            .map_or(0.0, |&(_, weight)| weight)
+    }
+
+    /// Draws a single advance value from the distribution.
+    pub fn sample(&self, rng: &mut impl Rng) -> u32 {
+        let mut x = rng.gen::<f64>();
+        for &(value, weight) in &self.outcomes {
+            if x < weight {
+                return value;
+            }
+            x -= weight;
+        }
+        self.outcomes.last().map_or(0, |&(value, _)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniform_sums_to_one() {
+        let d = DiceDistribution::uniform(6);
+        let total: f64 = d.outcomes().iter().map(|&(_, w)| w).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!((d.success_prob(3) - 1.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn two_dice_sum_matches_36_outcomes() {
+        let d = DiceDistribution::two_dice_sum();
+        assert!((d.success_prob(7) - 6.0 / 36.0).abs() < 1e-9);
+        assert!((d.success_prob(2) - 1.0 / 36.0).abs() < 1e-9);
+        let total: f64 = d.outcomes().iter().map(|&(_, w)| w).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_negative_weight() {
+        assert_eq!(
+            DiceDistribution::new(vec![(1, -0.5), (2, 1.5)]).unwrap_err(),
+            DiceDistributionError::NegativeWeight(-0.5)
+        );
+    }
+
+    #[test]
+    fn rejects_weights_not_summing_to_one() {
+        assert_eq!(
+            DiceDistribution::new(vec![(1, 0.5), (2, 0.2)]).unwrap_err(),
+            DiceDistributionError::WeightsDontSumToOne(0.7)
+        );
+    }
+}
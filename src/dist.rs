@@ -0,0 +1,114 @@
+//! Full turns-to-goal distribution, solved by value iteration instead of
+//! the single fixed point `Solver::solve` gives, since a busted turn resets
+//! progress and makes the turn count a heavy-tailed random variable that a
+//! mean alone hides.
+
+use crate::fft;
+
+/// A truncated probability-mass vector: `p[k]` is `P(#turns = k)` for `k`
+/// in `0..p.len()`; probability mass beyond the truncation is simply
+/// dropped, so `mass()` may be less than `1.0`.
+#[derive(Debug, Clone)]
+pub struct Dist {
+    p: Vec<f64>,
+}
+
+impl Dist {
+    pub fn zero(k: usize) -> Dist {
+        Dist { p: vec![0.0; k] }
+    }
+
+    /// A point mass on exactly `k` turns, truncated to `k + 1` coefficients.
+    pub fn unit(k: usize) -> Dist {
+        let mut p = vec![0.0; k + 1];
+        p[k] = 1.0;
+        Dist { p }
+    }
+
+    /// Total probability mass retained after truncation; `1.0 - mass()` is
+    /// the dropped tail.
+    pub fn mass(&self) -> f64 {
+        self.p.iter().sum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.p.iter().enumerate().map(|(k, p)| k as f64 * p).sum()
+    }
+
+    /// The smallest `k` at which the cumulative distribution reaches `q`.
+    pub fn percentile(&self, q: f64) -> usize {
+        let mut acc = 0.0;
+        for (k, &p) in self.p.iter().enumerate() {
+            acc += p;
+            if acc >= q {
+                return k;
+            }
+        }
+        self.p.len().saturating_sub(1)
+    }
+
+    fn scaled(&self, factor: f64) -> Dist {
+        Dist {
+            p: self.p.iter().map(|&x| x * factor).collect(),
+        }
+    }
+
+    /// `self + other * factor`, zero-extending the shorter vector.
+    pub fn add_scaled(&self, other: &Dist, factor: f64) -> Dist {
+        let scaled_other = other.scaled(factor);
+        let k = self.p.len().max(scaled_other.p.len());
+        let mut p = vec![0.0; k];
+        for (i, &x) in self.p.iter().enumerate() {
+            p[i] += x;
+        }
+        for (i, &x) in scaled_other.p.iter().enumerate() {
+            p[i] += x;
+        }
+        Dist { p }
+    }
+
+    /// Convolves `self` with `other` via FFT and truncates back to
+    /// `self.len()` coefficients, dropping mass that would fall beyond it.
+    pub fn convolve(&self, other: &Dist) -> Dist {
+        let k = self.p.len();
+        let mut p = fft::convolve(&self.p, &other.p);
+        p.truncate(k);
+        Dist { p }
+    }
+
+    /// Shifts the whole distribution one turn into the future: `z * P`.
+    pub fn shift_one_turn(&self) -> Dist {
+        self.convolve(&Dist::unit(1))
+    }
+
+    pub fn l1_distance(&self, other: &Dist) -> f64 {
+        let k = self.p.len().max(other.p.len());
+        (0..k)
+            .map(|i| {
+                let a = self.p.get(i).copied().unwrap_or(0.0);
+                let b = other.p.get(i).copied().unwrap_or(0.0);
+                (a - b).abs()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_moves_mean_by_one_within_capacity() {
+        let mut p = vec![0.0; 5];
+        p[1] = 1.0;
+        let d = Dist { p };
+        let shifted = d.shift_one_turn();
+        assert!((shifted.mean() - (d.mean() + 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn percentile_of_point_mass() {
+        let d = Dist::unit(5);
+        assert_eq!(d.percentile(0.5), 5);
+    }
+}
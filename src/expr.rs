@@ -39,6 +39,17 @@ impl Expr {
         }
     }
 
+    pub fn as_constant(&self) -> Option<f64> {
+        match self {
+            Sum {
+                one,
+                zero,
+                nonlinear,
+            } if *one == 0.0 && nonlinear.is_empty() => Some(*zero),
+            _ => None,
+        }
+    }
+
     pub fn eval(&self, x: f64) -> f64 {
         match self {
             Sum {
@@ -0,0 +1,122 @@
+//! Radix-2 iterative Cooley-Tukey FFT over `f64` complex pairs, used to
+//! convolve probability-mass vectors in `O(K log K)` instead of `O(K^2)`.
+
+use std::f64::consts::PI;
+
+#[derive(Debug, Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    const fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+/// In-place iterative radix-2 FFT; `a.len()` must be a power of two.
+/// `invert` selects the inverse transform (including the `1/n` scaling).
+fn fft(a: &mut [Complex], invert: bool) {
+    let n = a.len();
+    debug_assert!(n.is_power_of_two());
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let ang = 2.0 * PI / len as f64 * if invert { -1.0 } else { 1.0 };
+        let wlen = Complex::new(ang.cos(), ang.sin());
+        let mut i = 0;
+        while i < n {
+            let mut w = Complex::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let u = a[i + k];
+                let v = a[i + k + len / 2].mul(w);
+                a[i + k] = u.add(v);
+                a[i + k + len / 2] = u.sub(v);
+                w = w.mul(wlen);
+            }
+            i += len;
+        }
+        len <<= 1;
+    }
+
+    if invert {
+        for x in a.iter_mut() {
+            x.re /= n as f64;
+            x.im /= n as f64;
+        }
+    }
+}
+
+/// Convolves two real coefficient vectors via FFT, zero-padding to the next
+/// power of two, and returns all `a.len() + b.len() - 1` coefficients.
+pub fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let result_len = a.len() + b.len() - 1;
+    let n = result_len.next_power_of_two();
+
+    let mut fa: Vec<Complex> = a.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    fa.resize(n, Complex::new(0.0, 0.0));
+    let mut fb: Vec<Complex> = b.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    fb.resize(n, Complex::new(0.0, 0.0));
+
+    fft(&mut fa, false);
+    fft(&mut fb, false);
+    for i in 0..n {
+        fa[i] = fa[i].mul(fb[i]);
+    }
+    fft(&mut fa, true);
+
+    fa.iter().take(result_len).map(|c| c.re).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convolve_matches_naive() {
+        let a = [1.0, 2.0, 3.0];
+        let b = [4.0, 5.0];
+        let got = convolve(&a, &b);
+
+        let mut want = vec![0.0; a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                want[i + j] += x * y;
+            }
+        }
+
+        assert_eq!(got.len(), want.len());
+        for (g, w) in got.iter().zip(want.iter()) {
+            assert!((g - w).abs() < 1e-9);
+        }
+    }
+}
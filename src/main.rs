@@ -1,20 +1,80 @@
+mod dice;
+mod dist;
 mod expr;
+mod fft;
+mod parser;
+mod sim;
 
 use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
 
 use clap::Clap;
+use rand::thread_rng;
 
+use crate::dice::DiceDistribution;
+use crate::dist::Dist;
 use crate::expr::Expr;
 
-#[derive(Debug, Copy, Clone, Clap)]
+#[derive(Debug, Clone, Clap)]
 struct Opts {
     #[clap(short, long, default_value = "6")]
     dice: u32,
+    /// Use the triangular distribution of the sum of two fair six-sided
+    /// dice (values 2..=12) instead of a single uniform `dice`-sided die.
+    #[clap(long)]
+    two_dice: bool,
     #[clap(short, long, default_value = "20")]
     goal: u32,
+    /// Solve `x = f(x)` for a textual expression over `x` instead of running
+    /// the Can't Stop solver, e.g. `--expr "min(x/2 + 0.5, 2.0)"`.
+    #[clap(long)]
+    expr: Option<String>,
+    /// After solving, cross-check `solve(n)` against a Monte Carlo simulation
+    /// of the same policy for every start position, each bounded to this many
+    /// seconds of wall-clock time. Ignored if `sim_trials` is also given.
+    #[clap(long)]
+    sim_seconds: Option<f64>,
+    /// After solving, cross-check `solve(n)` against a Monte Carlo simulation
+    /// of the same policy for every start position, using exactly this many
+    /// trials instead of a wall-clock budget.
+    #[clap(long)]
+    sim_trials: Option<u64>,
+    /// After solving, also compute the full turns-to-goal distribution per
+    /// start position and print its mean and 50th/90th/99th percentiles.
+    #[clap(long)]
+    dist: bool,
+    /// Use an explicit discrete distribution instead of a uniform `dice`-sided
+    /// die or `--two-dice`, given as comma-separated `value:weight` pairs,
+    /// e.g. `--dice-weights "2:0.1,3:0.2,4:0.7"`. Weights must be
+    /// non-negative and sum to one. Takes precedence over `--two-dice` and
+    /// `--dice`.
+    #[clap(long)]
+    dice_weights: Option<String>,
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
+/// Parses `--dice-weights`'s `value:weight,value:weight,...` syntax into the
+/// `(u32, f64)` pairs [`DiceDistribution::new`] expects.
+fn parse_dice_weights(s: &str) -> Result<Vec<(u32, f64)>, String> {
+    s.split(',')
+        .map(|pair| {
+            let (value, weight) = pair
+                .split_once(':')
+                .ok_or_else(|| format!("expected `value:weight`, got `{}`", pair))?;
+            let value: u32 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid value `{}`", value))?;
+            let weight: f64 = weight
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid weight `{}`", weight))?;
+            Ok((value, weight))
+        })
+        .collect()
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 struct State {
     original_pos: u32,
     pos: u32,
@@ -57,45 +117,44 @@ impl State {
 
 #[derive(Debug)]
 struct Solver {
-    opts: Opts,
+    goal: u32,
+    dice: DiceDistribution,
     mem: HashMap<u32, f64>,
 }
 
 impl Solver {
-    pub fn new(opts: Opts) -> Solver {
-        let n = (opts.goal + 1) * (opts.goal + 1) * opts.dice;
+    pub fn new(goal: u32, dice: DiceDistribution) -> Solver {
+        let n = (goal + 1) * (goal + 1) * dice.outcomes().len() as u32;
         Solver {
-            opts,
+            goal,
+            dice,
             mem: HashMap::with_capacity(n as usize),
         }
     }
 
-    pub fn solve(&mut self, n: u32) -> f64 {
-        self.dfs_a(State::new(n))
+    pub fn goal(&self) -> u32 {
+        self.goal
     }
 
-    pub fn strategy(&mut self, n: u32, dice: u32) -> (f64, f64) {
-        let state = State::new(n).dice(dice);
-        let stop_s = self.dfs_a(state.stop());
-        let total_s = self.dfs_b(state).bisect();
-        (total_s, stop_s)
+    pub fn dice(&self) -> &DiceDistribution {
+        &self.dice
     }
 
-    const fn dice_n(&self) -> f64 {
-        self.opts.dice as f64
+    pub fn solve(&mut self, n: u32) -> f64 {
+        self.dfs_a(State::new(n))
     }
 
     fn dfs_a(&mut self, state: State) -> f64 {
         assert!(state.dice.is_none());
-        if state.pos >= self.opts.goal {
+        if state.pos >= self.goal {
             return 0.0;
         }
         if let Some(result) = self.mem.get(&state.pos) {
             return result.clone();
         }
         let mut s = Expr::constant(1.0);
-        for dice in 1..=self.opts.dice {
-            s = s + self.dfs_b(state.dice(dice)) / self.dice_n();
+        for &(value, weight) in self.dice.outcomes().to_vec().iter() {
+            s = s + self.dfs_b(state.dice(value)) * weight;
         }
         let r = s.bisect();
         self.mem.insert(state.pos, r.clone());
@@ -104,29 +163,307 @@ impl Solver {
 
     fn dfs_b(&mut self, state: State) -> Expr {
         assert!(state.dice.is_some());
-        if state.pos >= self.opts.goal {
+        if state.pos >= self.goal {
             return Expr::constant(0.0);
         }
         let stop = self.dfs_a(state.stop());
         let go_success = self.dfs_b(state.go_success());
         let go_fail = Expr::self_consistent(1.0) + Expr::constant(1.0);
-        let go = go_success / self.dice_n() + go_fail * ((self.dice_n() - 1.0) / self.dice_n());
+        let s_v = self.dice.success_prob(state.dice.unwrap());
+        let go = go_success * s_v + go_fail * (1.0 - s_v);
+        go.min(Expr::constant(stop))
+    }
+
+    /// Same go/stop recipe as [`Solver::dfs_a`]/[`Solver::dfs_b`], but
+    /// read-only: it assumes `mem` has already been warmed for every
+    /// position reachable by `stop()` and never mutates it, so it can be
+    /// called concurrently from a shared `&Solver`.
+    pub fn strategy_warmed(&self, n: u32, dice: u32) -> (f64, f64) {
+        let state = State::new(n).dice(dice);
+        let stop_s = self.dfs_a_warmed(state.stop());
+        let total_s = self.dfs_b_warmed(state).bisect();
+        (total_s, stop_s)
+    }
+
+    fn dfs_a_warmed(&self, state: State) -> f64 {
+        assert!(state.dice.is_none());
+        if state.pos >= self.goal {
+            return 0.0;
+        }
+        *self.mem.get(&state.pos).expect(
+            "mem must be warmed for every position reachable by stop() before parallel dispatch",
+        )
+    }
+
+    fn dfs_b_warmed(&self, state: State) -> Expr {
+        assert!(state.dice.is_some());
+        if state.pos >= self.goal {
+            return Expr::constant(0.0);
+        }
+        let stop = self.dfs_a_warmed(state.stop());
+        let go_success = self.dfs_b_warmed(state.go_success());
+        let go_fail = Expr::self_consistent(1.0) + Expr::constant(1.0);
+        let s_v = self.dice.success_prob(state.dice.unwrap());
+        let go = go_success * s_v + go_fail * (1.0 - s_v);
         go.min(Expr::constant(stop))
     }
+
+    /// Solves the full turns-to-goal distribution from every start position
+    /// by value iteration, truncated to `k_max` coefficients. Requires `mem`
+    /// to already be warmed, since the go/stop decision at every node reuses
+    /// the same policy as `strategy_warmed`.
+    pub fn turn_distributions(&self, k_max: usize, eps: f64) -> Vec<Dist> {
+        let goal = self.goal;
+        let mut dists = vec![Dist::zero(k_max); goal as usize];
+        for _ in 0..10_000 {
+            let mut next = Vec::with_capacity(goal as usize);
+            for start in 0..goal {
+                let mut turn = Dist::zero(k_max);
+                for &(value, weight) in self.dice.outcomes() {
+                    let state = State {
+                        original_pos: start,
+                        pos: start + value,
+                        dice: Some(value),
+                    };
+                    let branch = self.dfs_b_dist(state, &dists, k_max);
+                    turn = turn.add_scaled(&branch, weight);
+                }
+                next.push(turn.shift_one_turn());
+            }
+            let delta = dists
+                .iter()
+                .zip(next.iter())
+                .map(|(a, b)| a.l1_distance(b))
+                .fold(0.0, f64::max);
+            dists = next;
+            if delta < eps {
+                break;
+            }
+        }
+        dists
+    }
+
+    /// Like [`Solver::turn_distributions`], but grows `k_max` until the
+    /// worst-case dropped tail mass across all start positions is below
+    /// `tail_threshold`, or `max_k` is reached.
+    pub fn turn_distributions_adaptive(
+        &self,
+        eps: f64,
+        tail_threshold: f64,
+        max_k: usize,
+    ) -> Vec<Dist> {
+        let mut k = 64.min(max_k.max(1));
+        loop {
+            let dists = self.turn_distributions(k, eps);
+            let worst_tail = dists.iter().map(|d| 1.0 - d.mass()).fold(0.0, f64::max);
+            if worst_tail <= tail_threshold || k >= max_k {
+                return dists;
+            }
+            k = (k * 2).min(max_k);
+        }
+    }
+
+    /// The distribution of additional turns needed starting mid-turn at
+    /// `state`, choosing go vs. stop with the same policy as
+    /// `strategy_warmed`: a point mass on zero more turns if this roll
+    /// already reaches the goal, the resulting state's distribution for a
+    /// stop, or recursing into another roll weighted by the model's success
+    /// probability and an unshifted bust back to the start-of-turn
+    /// distribution (the outer loop in `turn_distributions` applies the
+    /// one-turn shift for the whole turn exactly once).
+    fn dfs_b_dist(&self, state: State, dists: &[Dist], k_max: usize) -> Dist {
+        if state.pos >= self.goal {
+            return Dist::unit(0);
+        }
+        let stop_s = self.dfs_a_warmed(state.stop());
+        let go_s = self.dfs_b_warmed(state).bisect();
+        if go_s < stop_s {
+            let go_success = self.dfs_b_dist(state.go_success(), dists, k_max);
+            let go_fail = dists[state.original_pos as usize].clone();
+            let s_v = self.dice.success_prob(state.dice.unwrap());
+            Dist::zero(k_max)
+                .add_scaled(&go_success, s_v)
+                .add_scaled(&go_fail, 1.0 - s_v)
+        } else {
+            dists[state.pos as usize].clone()
+        }
+    }
+}
+
+/// `(n, per-dice-value (total_s, stop_s) strategy)` row for a single start
+/// position, as produced by [`Worker::run`].
+type StrategyRow = (u32, Vec<(u32, (f64, f64))>);
+
+/// A contiguous slice `[start, end)` of start positions, handed to one
+/// thread so the `(n, d)` grid can be computed without locking once `mem`
+/// is frozen.
+struct Worker {
+    start: u32,
+    end: u32,
+}
+
+impl Worker {
+    /// Splits `0..goal` into at most `num_workers` contiguous chunks.
+    fn split(goal: u32, num_workers: usize) -> Vec<Worker> {
+        let num_workers = num_workers.max(1) as u32;
+        let chunk_size = goal.div_ceil(num_workers);
+        let mut workers = Vec::new();
+        let mut start = 0;
+        while start < goal {
+            let end = (start + chunk_size).min(goal);
+            workers.push(Worker { start, end });
+            start = end;
+        }
+        workers
+    }
+
+    fn run(&self, solver: &Solver) -> Vec<StrategyRow> {
+        (self.start..self.end)
+            .map(|n| {
+                let row = solver
+                    .dice()
+                    .outcomes()
+                    .iter()
+                    .map(|&(value, _)| (value, solver.strategy_warmed(n, value)))
+                    .collect();
+                (n, row)
+            })
+            .collect()
+    }
 }
 
 fn main() {
     let opts: Opts = Opts::parse();
+    if let Some(expr) = &opts.expr {
+        match parser::solve(expr) {
+            Ok(x) => println!("{}", x),
+            Err(e) => eprintln!("failed to parse expression: {:?}", e),
+        }
+        return;
+    }
     let goal = opts.goal;
-    let mut solver = Solver::new(opts);
+    let sim_seconds = opts.sim_seconds;
+    let sim_trials = opts.sim_trials;
+    let dist = opts.dist;
+    let dice = if let Some(weights) = &opts.dice_weights {
+        let outcomes = match parse_dice_weights(weights) {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                eprintln!("failed to parse --dice-weights: {}", e);
+                return;
+            }
+        };
+        match DiceDistribution::new(outcomes) {
+            Ok(dice) => dice,
+            Err(e) => {
+                eprintln!("invalid --dice-weights: {:?}", e);
+                return;
+            }
+        }
+    } else if opts.two_dice {
+        DiceDistribution::two_dice_sum()
+    } else {
+        DiceDistribution::uniform(opts.dice)
+    };
+    let mut solver = Solver::new(goal, dice);
     for i in 0..goal {
         println!("{} {}", i, solver.solve(i));
     }
-    for n in 0..goal {
-        print!("{}", n);
-        for d in 1..=opts.dice {
-            print!(" {:?}", solver.strategy(n, d));
+
+    if let Some(trials) = sim_trials {
+        let mut rng = thread_rng();
+        for n in 0..goal {
+            let analytic = solver.solve(n);
+            let empirical = sim::run_trials(&solver, solver.dice(), goal, n, trials, &mut rng);
+            println!(
+                "sim {} analytic={} empirical={}+-{} trials={}",
+                n, analytic, empirical.mean, empirical.stderr, empirical.trials
+            );
+        }
+    } else if let Some(seconds) = sim_seconds {
+        if !seconds.is_finite() || seconds < 0.0 {
+            eprintln!("invalid --sim-seconds: {} is not a finite, non-negative number", seconds);
+            return;
         }
-        println!();
+        let mut rng = thread_rng();
+        let budget = Duration::from_secs_f64(seconds);
+        for n in 0..goal {
+            let analytic = solver.solve(n);
+            let empirical = sim::run_for(&solver, solver.dice(), goal, n, budget, &mut rng);
+            println!(
+                "sim {} analytic={} empirical={}+-{} trials={}",
+                n, analytic, empirical.mean, empirical.stderr, empirical.trials
+            );
+        }
+    }
+
+    if dist {
+        let dists = solver.turn_distributions_adaptive(1e-6, 1e-4, 4096);
+        for (n, d) in dists.iter().enumerate() {
+            println!(
+                "dist {} mean={} p50={} p90={} p99={} mass={}",
+                n,
+                d.mean(),
+                d.percentile(0.5),
+                d.percentile(0.9),
+                d.percentile(0.99),
+                d.mass()
+            );
+        }
+    }
+
+    // `mem` is fully warmed for every position reachable by `stop()`, so the
+    // grid below never touches it mutably and can be split across threads
+    // without a lock.
+    let num_workers = thread::available_parallelism()
+        .map(std::num::NonZeroUsize::get)
+        .unwrap_or(1);
+    let workers = Worker::split(solver.goal(), num_workers);
+    let solver = &solver;
+    let chunks = thread::scope(|s| {
+        let handles: Vec<_> = workers
+            .iter()
+            .map(|w| s.spawn(move || w.run(solver)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    for chunk in chunks {
+        for (n, row) in chunk {
+            print!("{}", n);
+            for (value, s) in row {
+                print!(" {}:{:?}", value, s);
+            }
+            println!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dice_weights_parses_value_weight_pairs() {
+        let outcomes = parse_dice_weights("2:0.1,3:0.2,4:0.7").unwrap();
+        assert_eq!(outcomes, vec![(2, 0.1), (3, 0.2), (4, 0.7)]);
+    }
+
+    #[test]
+    fn parse_dice_weights_rejects_missing_colon() {
+        assert!(parse_dice_weights("2-0.1").is_err());
+    }
+
+    #[test]
+    fn parse_dice_weights_rejects_non_numeric_value() {
+        assert!(parse_dice_weights("x:0.1").is_err());
+    }
+
+    #[test]
+    fn parse_dice_weights_rejects_non_numeric_weight() {
+        assert!(parse_dice_weights("2:abc").is_err());
     }
 }
@@ -0,0 +1,235 @@
+//! Recursive-descent parser for a small expression language over a single
+//! self-reference variable `x`, used to describe a fixed-point equation
+//! `x = f(x)` without editing Rust.
+//!
+//! Grammar:
+//! ```text
+//! expression := term (('+') term)*
+//! term       := factor (('*'|'/') factor)*
+//! factor     := number | 'x' | 'min' '(' expression ',' expression ')' | '(' expression ')'
+//! ```
+
+use crate::expr::Expr;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    LParen,
+    RParen,
+    Comma,
+    Plus,
+    Star,
+    Slash,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    UnexpectedChar(char),
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    NonConstantScale,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' | '\r' => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            '+' => {
+                chars.next();
+                tokens.push(Token::Plus);
+            }
+            '*' => {
+                chars.next();
+                tokens.push(Token::Star);
+            }
+            '/' => {
+                chars.next();
+                tokens.push(Token::Slash);
+            }
+            '0'..='9' | '.' => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let n = s
+                    .parse::<f64>()
+                    .map_err(|_| ParseError::UnexpectedChar(c))?;
+                tokens.push(Token::Number(n));
+            }
+            c if c.is_alphabetic() => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(s));
+            }
+            c => return Err(ParseError::UnexpectedChar(c)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn expect(&mut self, t: &Token) -> Result<(), ParseError> {
+        match self.next() {
+            Some(ref got) if got == t => Ok(()),
+            Some(got) => Err(ParseError::UnexpectedToken(format!("{:?}", got))),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+
+    fn parse_expression(&mut self) -> Result<Expr, ParseError> {
+        let mut e = self.parse_term()?;
+        while let Some(Token::Plus) = self.peek() {
+            self.next();
+            e = e + self.parse_term()?;
+        }
+        Ok(e)
+    }
+
+    fn parse_term(&mut self) -> Result<Expr, ParseError> {
+        let mut e = self.parse_factor()?;
+        loop {
+            match self.peek() {
+                Some(Token::Star) => {
+                    self.next();
+                    let rhs = self.parse_factor()?;
+                    e = scale(e, rhs)?;
+                }
+                Some(Token::Slash) => {
+                    self.next();
+                    let rhs = self.parse_factor()?;
+                    let c = rhs.as_constant().ok_or(ParseError::NonConstantScale)?;
+                    e = e / c;
+                }
+                _ => break,
+            }
+        }
+        Ok(e)
+    }
+
+    fn parse_factor(&mut self) -> Result<Expr, ParseError> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::constant(n)),
+            Some(Token::Ident(ref s)) if s == "x" => Ok(Expr::self_consistent(1.0)),
+            Some(Token::Ident(ref s)) if s == "min" => {
+                self.expect(&Token::LParen)?;
+                let a = self.parse_expression()?;
+                self.expect(&Token::Comma)?;
+                let b = self.parse_expression()?;
+                self.expect(&Token::RParen)?;
+                Ok(a.min(b))
+            }
+            Some(Token::LParen) => {
+                let e = self.parse_expression()?;
+                self.expect(&Token::RParen)?;
+                Ok(e)
+            }
+            Some(t) => Err(ParseError::UnexpectedToken(format!("{:?}", t))),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+}
+
+/// `Mul` is only defined between an `Expr` and an `f64`, so one side of a
+/// `*` must reduce to a constant; fold it into the other side, or reject
+/// the expression if neither does.
+fn scale(a: Expr, b: Expr) -> Result<Expr, ParseError> {
+    if let Some(c) = a.as_constant() {
+        Ok(b * c)
+    } else if let Some(c) = b.as_constant() {
+        Ok(a * c)
+    } else {
+        Err(ParseError::NonConstantScale)
+    }
+}
+
+/// Parses `input` as an expression over `x` and builds the corresponding
+/// [`Expr`].
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let e = parser.parse_expression()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(ParseError::UnexpectedToken(format!(
+            "{:?}",
+            parser.tokens[parser.pos]
+        )));
+    }
+    Ok(e)
+}
+
+/// Parses `input` as the right-hand side `f(x)` of `x = f(x)` and solves for
+/// the fixed point via bisection.
+pub fn solve(input: &str) -> Result<f64, ParseError> {
+    Ok(parse(input)?.bisect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_constant() {
+        assert_eq!(solve("2.0").unwrap(), 2.0);
+    }
+
+    #[test]
+    fn parse_self_reference() {
+        // x = x/2 + 0.5 => x = 1.0
+        assert!((solve("x/2 + 0.5").unwrap() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parse_min() {
+        // x = min(x/2 + 0.5, 2.0) => x = 1.0
+        assert!((solve("min(x/2 + 0.5, 2.0)").unwrap() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reject_nonconstant_scale() {
+        assert_eq!(parse("x * x").unwrap_err(), ParseError::NonConstantScale);
+    }
+}
@@ -0,0 +1,148 @@
+//! Monte Carlo validator: plays the 1D game under the policy implied by
+//! [`Solver::strategy_warmed`] and reports empirical turn statistics, so the
+//! analytic `solve` values can be cross-checked against simulation.
+
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::dice::DiceDistribution;
+use crate::Solver;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SimResult {
+    pub trials: u64,
+    pub mean: f64,
+    pub stderr: f64,
+}
+
+impl SimResult {
+    fn from_turns(trials: u64, sum: f64, sum_sq: f64) -> SimResult {
+        let n = trials as f64;
+        let mean = sum / n;
+        let variance = (sum_sq / n - mean * mean).max(0.0);
+        SimResult {
+            trials,
+            mean,
+            stderr: (variance / n).sqrt(),
+        }
+    }
+}
+
+/// Plays one full game from `start` to `goal` under the policy implied by
+/// `strategy_warmed`, returning the number of turns taken.
+///
+/// Each turn draws the first advance value from `dice`, then repeatedly
+/// compares the go-value against the stop-value from the `(n, d)` strategy
+/// at the current landing to decide whether to keep re-rolling: a re-roll
+/// succeeds with `dice`'s success probability for that value, and
+/// otherwise busts the turn, resetting to the turn's start position.
+fn play_game(
+    solver: &Solver,
+    dice: &DiceDistribution,
+    goal: u32,
+    start: u32,
+    rng: &mut impl Rng,
+) -> u64 {
+    let mut pos = start;
+    let mut turns = 0u64;
+    while pos < goal {
+        turns += 1;
+        let turn_start = pos;
+        let d = dice.sample(rng);
+        let s_v = dice.success_prob(d);
+        let mut cur = turn_start + d;
+        loop {
+            let (total_s, stop_s) = solver.strategy_warmed(cur - d, d);
+            if total_s >= stop_s {
+                break;
+            }
+            if rng.gen_bool(s_v) {
+                cur += d;
+            } else {
+                cur = turn_start;
+                break;
+            }
+        }
+        pos = cur;
+    }
+    turns
+}
+
+/// Runs exactly `trials` simulated games from `start` and returns the
+/// empirical mean turn count and its standard error.
+pub fn run_trials(
+    solver: &Solver,
+    dice: &DiceDistribution,
+    goal: u32,
+    start: u32,
+    trials: u64,
+    rng: &mut impl Rng,
+) -> SimResult {
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    for _ in 0..trials {
+        let turns = play_game(solver, dice, goal, start, rng) as f64;
+        sum += turns;
+        sum_sq += turns * turns;
+    }
+    SimResult::from_turns(trials, sum, sum_sq)
+}
+
+/// Runs as many simulated games from `start` as fit in `budget` wall-clock
+/// time, polling the elapsed time between games, and returns the empirical
+/// mean turn count and its standard error.
+pub fn run_for(
+    solver: &Solver,
+    dice: &DiceDistribution,
+    goal: u32,
+    start: u32,
+    budget: Duration,
+    rng: &mut impl Rng,
+) -> SimResult {
+    let started = Instant::now();
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    let mut trials = 0u64;
+    while started.elapsed() < budget {
+        let turns = play_game(solver, dice, goal, start, rng) as f64;
+        sum += turns;
+        sum_sq += turns * turns;
+        trials += 1;
+    }
+    SimResult::from_turns(trials.max(1), sum, sum_sq)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::Solver;
+
+    use super::*;
+
+    #[test]
+    fn run_trials_mean_lands_within_a_few_stderrs_of_solve() {
+        let goal = 4;
+        let dice = DiceDistribution::uniform(3);
+        let mut solver = Solver::new(goal, dice.clone());
+        for n in 0..goal {
+            solver.solve(n);
+        }
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in 0..goal {
+            let analytic = solver.solve(n);
+            let empirical = run_trials(&solver, &dice, goal, n, 20_000, &mut rng);
+            let tolerance = 6.0 * empirical.stderr.max(1e-9);
+            assert!(
+                (empirical.mean - analytic).abs() < tolerance,
+                "n={} analytic={} empirical={}+-{}",
+                n,
+                analytic,
+                empirical.mean,
+                empirical.stderr
+            );
+        }
+    }
+}